@@ -0,0 +1,26 @@
+extern crate gl_generator;
+extern crate cfg_aliases;
+
+use gl_generator::{Api, Fallbacks, Profile, Registry, StructGenerator};
+use std::env;
+use std::fs::File;
+use std::path::Path;
+
+fn main() {
+    cfg_aliases::cfg_aliases! {
+        wayland_platform: { all(unix, not(target_os = "macos"), feature = "wayland") },
+        egl: { all(unix, not(target_os = "macos"), feature = "egl") },
+        wasm_platform: { target_family = "wasm" },
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let mut file = File::create(Path::new(&out_dir).join("gl_bindings.rs")).unwrap();
+
+    // Generated against the GL 4.3 core registry rather than 3.3: the
+    // compute-shader/SSBO demo added in chunk0-5 needs GL_ARB_compute_shader,
+    // which 3.3 core doesn't expose, and the context this builds against
+    // (see ContextAttributesBuilder in main.rs) is requested at 4.3 to match.
+    Registry::new(Api::Gl, (4, 3), Profile::Core, Fallbacks::All, [])
+        .write_bindings(StructGenerator, &mut file)
+        .unwrap();
+}
@@ -0,0 +1,146 @@
+// GPGPU compute path: Shader Storage Buffer Objects plus a compute shader
+// dispatch helper, demonstrated with a ping-pong Conway's Game of Life
+// automaton that a fullscreen quad samples straight out of the live SSBO.
+
+use crate::gl;
+use crate::gl::types::*;
+use crate::{GLShader, compile_shaders_vec, link_shaders_vec};
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+use std::rc::Rc;
+
+unsafe fn build_compute_program(gl: &gl::Gl, path: &Path) -> Result<GLuint, String> {
+    let shader = GLShader::from_file(path, gl::COMPUTE_SHADER)?;
+    let compiled = compile_shaders_vec(gl, vec![shader])?;
+    link_shaders_vec(gl, compiled)
+}
+
+// Allocates a Shader Storage Buffer Object seeded with `data` and binds it to
+// `binding` for use by both compute and fragment stages.
+unsafe fn create_ssbo(gl: &gl::Gl, binding: GLuint, data: &[u32]) -> GLuint {
+    let mut ssbo = 0;
+    gl.GenBuffers(1, &mut ssbo);
+    gl.BindBuffer(gl::SHADER_STORAGE_BUFFER, ssbo);
+    gl.BufferData(gl::SHADER_STORAGE_BUFFER,
+        mem::size_of_val(data) as GLsizeiptr,
+        data.as_ptr() as *const c_void,
+        gl::DYNAMIC_COPY);
+    gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, ssbo);
+    gl.BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+    ssbo
+}
+
+unsafe fn dispatch_compute(gl: &gl::Gl, x: u32, y: u32, z: u32) {
+    gl.DispatchCompute(x, y, z);
+    gl.MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT);
+}
+
+pub struct Automaton {
+    gl: Rc<gl::Gl>,
+    compute_program: GLuint,
+    render_program: GLuint,
+    ssbo: [GLuint; 2],
+    quad_vao: u32,
+    quad_vbo: u32,
+    grid_width: u32,
+    grid_height: u32,
+    front: usize
+}
+
+impl Automaton {
+    pub unsafe fn initialize(gl: Rc<gl::Gl>, shader_dir: &str, grid_width: u32, grid_height: u32) -> Self {
+        let compute_program = build_compute_program(&gl, &Path::new(shader_dir).join("automaton.comp"))
+            .expect("Failed to build automaton compute program");
+        let render_program = crate::build_shader_program(&gl,
+            &Path::new(shader_dir).join("automaton.vert"),
+            &Path::new(shader_dir).join("automaton.frag"))
+            .expect("Failed to build automaton render program");
+
+        let cell_count = (grid_width * grid_height) as usize;
+        let initial: Vec<u32> = (0..cell_count).map(|i| (i * 2654435761 % 5 == 0) as u32).collect();
+        let ssbo = [create_ssbo(&gl, 0, &initial), create_ssbo(&gl, 1, &vec![0u32; cell_count])];
+
+        // Fullscreen quad in NDC, two triangles.
+        let quad_vertices: [f32; 12] = [
+            -1.0, -1.0,
+             1.0, -1.0,
+             1.0,  1.0,
+            -1.0, -1.0,
+             1.0,  1.0,
+            -1.0,  1.0
+        ];
+        let (mut quad_vao, mut quad_vbo) = (0, 0);
+        gl.GenVertexArrays(1, &mut quad_vao);
+        gl.GenBuffers(1, &mut quad_vbo);
+        gl.BindVertexArray(quad_vao);
+        gl.BindBuffer(gl::ARRAY_BUFFER, quad_vbo);
+        gl.BufferData(gl::ARRAY_BUFFER,
+            mem::size_of_val(&quad_vertices) as GLsizeiptr,
+            &quad_vertices[0] as *const f32 as *const c_void,
+            gl::STATIC_DRAW);
+        gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 2 * mem::size_of::<GLfloat>() as GLsizei, ptr::null());
+        gl.EnableVertexAttribArray(0);
+        gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl.BindVertexArray(0);
+
+        Self {
+            gl,
+            compute_program,
+            render_program,
+            ssbo,
+            quad_vao,
+            quad_vbo,
+            grid_width,
+            grid_height,
+            front: 0
+        }
+    }
+
+    // Advances the automaton one generation: the compute shader reads the
+    // front SSBO and writes the next generation into the back one, then the
+    // two swap so the back buffer becomes the new front.
+    pub unsafe fn step(&mut self) {
+        let gl = &self.gl;
+        let back = 1 - self.front;
+        gl.UseProgram(self.compute_program);
+        self.set_grid_size_uniform(self.compute_program);
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, self.ssbo[self.front]);
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 1, self.ssbo[back]);
+
+        let groups_x = self.grid_width.div_ceil(16);
+        let groups_y = self.grid_height.div_ceil(16);
+        dispatch_compute(gl, groups_x, groups_y, 1);
+
+        self.front = back;
+    }
+
+    pub unsafe fn render(&self) {
+        let gl = &self.gl;
+        gl.UseProgram(self.render_program);
+        self.set_grid_size_uniform(self.render_program);
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, self.ssbo[self.front]);
+        gl.BindVertexArray(self.quad_vao);
+        gl.DrawArrays(gl::TRIANGLES, 0, 6);
+    }
+
+    unsafe fn set_grid_size_uniform(&self, program: GLuint) {
+        let c_name = CString::new("grid_size").unwrap();
+        let location = self.gl.GetUniformLocation(program, c_name.as_ptr());
+        self.gl.Uniform2i(location, self.grid_width as GLint, self.grid_height as GLint);
+    }
+}
+
+impl Drop for Automaton {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteBuffers(2, self.ssbo.as_ptr());
+            self.gl.DeleteBuffers(1, &self.quad_vbo);
+            self.gl.DeleteVertexArrays(1, &self.quad_vao);
+            self.gl.DeleteProgram(self.render_program);
+            self.gl.DeleteProgram(self.compute_program);
+        }
+    }
+}
@@ -1,238 +1,650 @@
-extern crate glfw;
-extern crate gl;
+extern crate winit;
+extern crate glutin;
+extern crate glutin_winit;
+extern crate raw_window_handle;
+extern crate image;
+extern crate jxl_oxide;
+extern crate cgmath;
+extern crate freetype;
+
+mod text;
+mod compute;
+
+// gl_generator's StructGenerator output isn't written with clippy in mind;
+// don't hold generated code to our own lint bar.
+#[allow(clippy::all)]
+mod gl {
+    include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
+}
 
-use self::glfw::{Context, Key, Action};
 use self::gl::types::*;
-use std::sync::mpsc::Receiver;
+use self::cgmath::{Matrix4, Vector3, Point3, Deg, PerspectiveFov, Rad, Matrix, SquareMatrix};
+use self::glutin::config::ConfigTemplateBuilder;
+use self::glutin::context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext, Version};
+use self::glutin::display::GetGlDisplay;
+use self::glutin::prelude::*;
+use self::glutin::surface::{GlSurface, Surface, SwapInterval, WindowSurface};
+use self::glutin_winit::{DisplayBuilder, GlWindow};
+use self::raw_window_handle::HasRawWindowHandle;
+use self::winit::event::{ElementState, Event, WindowEvent};
+use self::winit::event_loop::EventLoop;
+use self::winit::keyboard::{KeyCode, PhysicalKey};
+use self::winit::window::{Window, WindowBuilder};
 use std::ffi::CString;
+use std::num::NonZeroU32;
 use std::ptr;
+use std::rc::Rc;
 use std::str;
 use std::mem;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use std::os::raw::c_void;
 
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
+const SHADER_DIR: &str = "shaders";
+const TEXTURE_PATH: &str = "textures/quad.png";
+const FONT_PATH: &str = "fonts/default.ttf";
+const FONT_PIXEL_SIZE: u32 = 32;
+const AUTOMATON_GRID_WIDTH: u32 = 256;
+const AUTOMATON_GRID_HEIGHT: u32 = 256;
+
+pub(crate) struct GLShader {
+    shader_src: String,
+    shader_type: GLenum
+}
 
-const VERT_SHADER_SRC: &str = r#"
-    #version 330 core
-    layout (location = 0) in vec3 aPos;
-    void main() {
-        gl_Position = vec4(aPos.x, aPos.y, aPos.z, 1.0);
-    }
-"#;
+impl GLShader {
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P, shader_type: GLenum) -> Result<Self, String> {
+        let shader_src = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read shader {}: {}", path.as_ref().display(), e))?;
 
-const FRAG_SHADER_SRC: &str = r#"
-    #version 330 core
-    out vec4 FragColor;
-    void main() {
-        FragColor = vec4(1.0f, 0.5f, 0.2f, 1.0f);
+        Ok(Self {
+            shader_src,
+            shader_type
+        })
     }
-"#;
 
-struct GLShader<'a> {
-    shader_src: &'a str,
-    shader_type: GLenum
-}
-
-impl GLShader<'_> {
-    unsafe fn compile(&self) -> GLuint {
+    unsafe fn compile(&self, gl: &gl::Gl) -> Result<GLuint, String> {
         let mut success = gl::FALSE as GLint;
         let mut info_log: Vec<u8> = Vec::with_capacity(512);
-        let shader = gl::CreateShader(self.shader_type);
+        let shader = gl.CreateShader(self.shader_type);
         let c_str_source = CString::new(self.shader_src.as_bytes()).unwrap();
 
-        gl::ShaderSource(shader, 1, &c_str_source.as_ptr(), ptr::null());
-        gl::CompileShader(shader);
-        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+        gl.ShaderSource(shader, 1, &c_str_source.as_ptr(), ptr::null());
+        gl.CompileShader(shader);
+        gl.GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
 
         if success != gl::TRUE as GLint {
-            gl::GetShaderInfoLog(shader, 512, ptr::null_mut(), info_log.as_mut_ptr() as *mut GLchar);
-            println!("ERROR::SHADER::COMPILATION_FAILED\n{}", str::from_utf8(&info_log).unwrap());
+            gl.GetShaderInfoLog(shader, 512, ptr::null_mut(), info_log.as_mut_ptr() as *mut GLchar);
+            gl.DeleteShader(shader);
+            return Err(format!("ERROR::SHADER::COMPILATION_FAILED\n{}", str::from_utf8(&info_log).unwrap()));
         }
 
-        shader
+        Ok(shader)
     }
 }
 
-struct OpenGLContext {
-    shader_program: GLuint,
-    vao: u32,
-    vbo: u32,
-    ebo: u32
+// Reads, compiles and links a vertex/fragment shader pair from disk into a
+// program. Shared by `OpenGLContext`, `text::TextRenderer` and `compute::Automaton`.
+// Read failures (e.g. an editor's delete-then-rewrite on save racing a
+// hot-reload tick) are returned as errors rather than panicking, so callers
+// like `OpenGLContext::reload_shaders_if_changed` can keep the previous
+// program instead of crashing the app.
+pub(crate) unsafe fn build_shader_program(gl: &gl::Gl, vert_path: &Path, frag_path: &Path) -> Result<GLuint, String> {
+    let shader_vec: Vec<GLShader> = vec![
+        GLShader::from_file(vert_path, gl::VERTEX_SHADER)?,
+        GLShader::from_file(frag_path, gl::FRAGMENT_SHADER)?
+    ];
+
+    let compiled_shaders = compile_shaders_vec(gl, shader_vec)?;
+    link_shaders_vec(gl, compiled_shaders)
 }
 
-impl OpenGLContext {
-    unsafe fn initialize() -> Self {
-        let mut shader_vec: Vec<GLShader> = Vec::new();
-        shader_vec.push(GLShader {
-            shader_src: VERT_SHADER_SRC,
-            shader_type: gl::VERTEX_SHADER
-        });
+pub(crate) unsafe fn compile_shaders_vec(gl: &gl::Gl, shaders: Vec<GLShader>) -> Result<Vec<GLuint>, String> {
+    let mut compiled_shaders: Vec<GLuint> = Vec::new();
+    for shader in shaders {
+        match shader.compile(gl) {
+            Ok(s) => compiled_shaders.push(s),
+            Err(e) => {
+                for s in &compiled_shaders {
+                    gl.DeleteShader(*s);
+                }
+                return Err(e);
+            }
+        }
+    }
 
-        shader_vec.push(GLShader {
-            shader_src: FRAG_SHADER_SRC,
-            shader_type: gl::FRAGMENT_SHADER
-        });
+    Ok(compiled_shaders)
+}
 
-        let compiled_shaders = OpenGLContext::compile_shaders_vec(shader_vec);
-        let shader_program = OpenGLContext::link_shaders_vec(compiled_shaders);
-        
-        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
-        let verticies: [f32; 18] = [
-             // first triangle
-            -0.9, -0.5, 0.0,  // left
-            -0.0, -0.5, 0.0,  // right
-            -0.45, 0.5, 0.0,  // top
-            // second triangle
-            0.0, -0.5, 0.0,  // left
-            0.9, -0.5, 0.0,  // right
-            0.45, 0.5, 0.0   // top
-        ];
-        let indicies = [
-            0, 1, 3,
-            1, 2, 3
-        ];
+pub(crate) unsafe fn link_shaders_vec(gl: &gl::Gl, compiled_shaders: Vec<GLuint>) -> Result<GLuint, String> {
+    let mut success = gl::FALSE as GLint;
+    let mut info_log: Vec<u8> = Vec::with_capacity(512);
+    let shader_program: GLuint = gl.CreateProgram();
+
+    // Attatch the shaders
+    for i in &compiled_shaders {
+        gl.AttachShader(shader_program, *i);
+    }
+
+    gl.LinkProgram(shader_program);
+    gl.GetProgramiv(shader_program, gl::LINK_STATUS, &mut success);
+
+    let result = if success != gl::TRUE as GLint {
+        gl.GetProgramInfoLog(shader_program, 512, ptr::null_mut(), info_log.as_mut_ptr() as *mut GLchar);
+        gl.DeleteProgram(shader_program);
+        Err(format!("ERROR::SHADER::PROGRAM::COMPILATION_FAILED\n{}", str::from_utf8(&info_log).unwrap()))
+    } else {
+        Ok(shader_program)
+    };
+
+    // Delete the shaders now that they're linked into the program (or discarded)
+    for i in &compiled_shaders {
+        gl.DeleteShader(*i);
+    }
+
+    result
+}
+
+struct GLTexture {
+    id: GLuint
+}
+
+impl GLTexture {
+    // Decodes the image at `path` (dispatching on file extension, with `.jxl`
+    // routed through jxl-oxide and everything else through the `image` crate)
+    // and uploads it as an RGBA8 2D texture with mipmaps. A missing or
+    // corrupt asset falls back to a placeholder checkerboard rather than
+    // crashing the whole application at startup.
+    unsafe fn from_file<P: AsRef<Path>>(gl: &gl::Gl, path: P) -> Self {
+        let (width, height, pixels) = GLTexture::decode_rgba8(path.as_ref())
+            .unwrap_or_else(|e| {
+                println!("WARNING::TEXTURE::LOAD_FAILED\n{}\nusing placeholder texture", e);
+                GLTexture::missing_texture_placeholder()
+            });
+
+        let mut id = 0;
+        gl.GenTextures(1, &mut id);
+        gl.BindTexture(gl::TEXTURE_2D, id);
+
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as GLint);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+
+        gl.TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as GLint, width as GLsizei, height as GLsizei,
+            0, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_ptr() as *const c_void);
+        gl.GenerateMipmap(gl::TEXTURE_2D);
+
+        gl.BindTexture(gl::TEXTURE_2D, 0);
+
+        Self { id }
+    }
+
+    fn decode_rgba8(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+        let is_jxl = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("jxl"))
+            .unwrap_or(false);
+
+        if is_jxl {
+            GLTexture::decode_jxl(path)
+        } else {
+            let img = image::open(path)
+                .map_err(|e| format!("Failed to decode texture {}: {}", path.display(), e))?
+                .to_rgba8();
+            let (width, height) = img.dimensions();
+            Ok((width, height, img.into_raw()))
+        }
+    }
+
+    fn decode_jxl(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+        let image = jxl_oxide::JxlImage::open_with_defaults(path)
+            .map_err(|e| format!("Failed to decode JPEG XL texture {}: {}", path.display(), e))?;
+
+        let width = image.width();
+        let height = image.height();
+
+        let render = image.render_frame(0)
+            .map_err(|e| format!("Failed to render JPEG XL texture {}: {}", path.display(), e))?;
+
+        let pixels = GLTexture::framebuffer_to_rgba8(&render.image_all_channels(), width, height);
+
+        Ok((width, height, pixels))
+    }
+
+    // A magenta/black checkerboard, the conventional "missing texture" marker,
+    // so an absent or unreadable asset degrades visibly instead of crashing.
+    fn missing_texture_placeholder() -> (u32, u32, Vec<u8>) {
+        const MAGENTA: [u8; 4] = [255, 0, 255, 255];
+        const BLACK: [u8; 4] = [0, 0, 0, 255];
+        let pixels = [MAGENTA, BLACK, BLACK, MAGENTA].concat();
+        (2, 2, pixels)
+    }
+
+    // jxl-oxide hands back interleaved f32 samples (one per channel per
+    // pixel) rather than u8, so convert them ourselves the way `image`'s decoders
+    // already do for us on every other format.
+    fn framebuffer_to_rgba8(framebuffer: &jxl_oxide::FrameBuffer, width: u32, height: u32) -> Vec<u8> {
+        let channels = framebuffer.channels();
+        let samples = framebuffer.buf();
+        let pixel_count = (width * height) as usize;
+        let mut pixels = Vec::with_capacity(pixel_count * 4);
+
+        let to_u8 = |sample: f32| (sample.clamp(0.0, 1.0) * 255.0).round() as u8;
+        for i in 0..pixel_count {
+            let r = to_u8(samples[i * channels]);
+            let g = if channels >= 3 { to_u8(samples[i * channels + 1]) } else { r };
+            let b = if channels >= 3 { to_u8(samples[i * channels + 2]) } else { r };
+            // A 2-channel buffer is gray+alpha, so the alpha sample sits right
+            // after gray rather than at the RGB layout's index 3.
+            let a = match channels {
+                2 => to_u8(samples[i * channels + 1]),
+                n if n >= 4 => to_u8(samples[i * channels + 3]),
+                _ => 255
+            };
+            pixels.extend_from_slice(&[r, g, b, a]);
+        }
+
+        pixels
+    }
+}
+
+// Describes one interleaved vertex attribute (e.g. a 3-float position or a
+// 2-float texcoord) so `Mesh::from_interleaved` can derive stride, offsets
+// and the matching `glVertexAttribPointer` calls instead of having them
+// hand-written per mesh.
+struct VertexAttribute {
+    components: GLint,
+    attrib_type: GLenum
+}
+
+impl VertexAttribute {
+    fn component_size(&self) -> usize {
+        match self.attrib_type {
+            gl::FLOAT => mem::size_of::<GLfloat>(),
+            other => panic!("VertexAttribute: unsupported attribute type {}", other)
+        }
+    }
+}
+
+// Owns a VAO/VBO/EBO triple for one piece of geometry: an interleaved vertex
+// buffer laid out per `layout`, and the index buffer that draws it.
+struct Mesh {
+    gl: Rc<gl::Gl>,
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    index_count: GLsizei
+}
 
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-        gl::GenBuffers(1, &mut ebo);
-        gl::BindVertexArray(vao);
+impl Mesh {
+    unsafe fn from_interleaved(gl: Rc<gl::Gl>, vertices: &[f32], indices: &[u32], layout: &[VertexAttribute]) -> Self {
+        let stride: GLsizei = layout.iter()
+            .map(|attrib| attrib.components as usize * attrib.component_size())
+            .sum::<usize>() as GLsizei;
 
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER,
-            (verticies.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-            &verticies[0] as *const f32 as *const c_void,
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        gl.GenVertexArrays(1, &mut vao);
+        gl.GenBuffers(1, &mut vbo);
+        gl.GenBuffers(1, &mut ebo);
+        gl.BindVertexArray(vao);
+
+        gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl.BufferData(gl::ARRAY_BUFFER,
+            mem::size_of_val(vertices) as GLsizeiptr,
+            vertices.as_ptr() as *const c_void,
             gl::STATIC_DRAW);
 
-        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-        gl::BufferData(gl::ELEMENT_ARRAY_BUFFER,
-            (indicies.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-            &indicies[0] as *const i32 as *const c_void,
+        gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+        gl.BufferData(gl::ELEMENT_ARRAY_BUFFER,
+            mem::size_of_val(indices) as GLsizeiptr,
+            indices.as_ptr() as *const c_void,
             gl::STATIC_DRAW);
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 3 * mem::size_of::<GLfloat>() as GLsizei, ptr::null());
-        gl::EnableVertexAttribArray(0);
-        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-        gl::BindVertexArray(0);
+        let mut offset: usize = 0;
+        for (location, attrib) in layout.iter().enumerate() {
+            gl.VertexAttribPointer(location as GLuint, attrib.components, attrib.attrib_type,
+                gl::FALSE, stride, offset as *const c_void);
+            gl.EnableVertexAttribArray(location as GLuint);
+            offset += attrib.components as usize * attrib.component_size();
+        }
+
+        gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl.BindVertexArray(0);
 
         Self {
-            shader_program: shader_program,
-            vao: vao,
-            vbo: vbo,
-            ebo: ebo
+            gl,
+            vao,
+            vbo,
+            ebo,
+            index_count: indices.len() as GLsizei
         }
     }
 
-    unsafe fn compile_shaders_vec(shaders: Vec<GLShader>) -> Vec<GLuint> {
-        let mut compiled_shaders: Vec<GLuint> = Vec::new();
-        for shader in shaders {
-            compiled_shaders.push(shader.compile());
-        }
+    unsafe fn draw(&self) {
+        self.gl.BindVertexArray(self.vao);
+        self.gl.DrawElements(gl::TRIANGLES, self.index_count, gl::UNSIGNED_INT, ptr::null());
+    }
+}
 
-        compiled_shaders
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteBuffers(1, &self.ebo);
+            self.gl.DeleteBuffers(1, &self.vbo);
+            self.gl.DeleteVertexArrays(1, &self.vao);
+        }
     }
+}
 
-    unsafe fn link_shaders_vec(compiled_shaders: Vec<GLuint>) -> GLuint {
-        let mut success = gl::FALSE as GLint;
-        let mut info_log: Vec<u8> = Vec::with_capacity(512);
-        let shader_program: GLuint = gl::CreateProgram();
+struct OpenGLContext {
+    gl: Rc<gl::Gl>,
+    shader_program: GLuint,
+    shader_dir: PathBuf,
+    shader_mtimes: (SystemTime, SystemTime),
+    texture: GLTexture,
+    meshes: Vec<Mesh>
+}
 
-        // Attatch the shaders
-        for i in &compiled_shaders {
-            gl::AttachShader(shader_program, *i);
+impl OpenGLContext {
+    unsafe fn initialize(gl: Rc<gl::Gl>, shader_dir: &str, texture_path: &str) -> Self {
+        gl.Enable(gl::BLEND);
+        gl.BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+        let shader_dir = PathBuf::from(shader_dir);
+        let (vert_path, frag_path) = OpenGLContext::shader_paths(&shader_dir);
+        let shader_program = build_shader_program(&gl, &vert_path, &frag_path)
+            .expect("Failed to build initial shader program");
+        let shader_mtimes = OpenGLContext::shader_mtimes(&vert_path, &frag_path);
+        let texture = GLTexture::from_file(&gl, texture_path);
+
+        // Each vertex is position (3 floats) + texcoord (2 floats), interleaved.
+        let verticies: [f32; 30] = [
+             // first triangle                 // texcoord
+            -0.9, -0.5, 0.0,                    0.0, 0.0,  // left
+            -0.0, -0.5, 0.0,                    1.0, 0.0,  // right
+            -0.45, 0.5, 0.0,                    0.5, 1.0,  // top
+            // second triangle
+            0.0, -0.5, 0.0,                     0.0, 0.0,  // left
+            0.9, -0.5, 0.0,                     1.0, 0.0,  // right
+            0.45, 0.5, 0.0,                      0.5, 1.0   // top
+        ];
+        let indicies: [u32; 6] = [0, 1, 2, 3, 4, 5];
+        let layout = [
+            VertexAttribute { components: 3, attrib_type: gl::FLOAT },
+            VertexAttribute { components: 2, attrib_type: gl::FLOAT }
+        ];
+        let mesh = Mesh::from_interleaved(gl.clone(), &verticies, &indicies, &layout);
+
+        Self {
+            gl,
+            shader_program,
+            shader_dir,
+            shader_mtimes,
+            texture,
+            meshes: vec![mesh]
         }
+    }
 
-        gl::LinkProgram(shader_program);
-        gl::GetProgramiv(shader_program, gl::LINK_STATUS, &mut success);
-        if success != gl::TRUE as GLint {
-            gl::GetProgramInfoLog(shader_program, 512, ptr::null_mut(), info_log.as_mut_ptr() as *mut GLchar);
-            println!("ERROR::SHADER::PROGRAM::COMPILATION_FAILED\n{}", str::from_utf8(&info_log).unwrap());
+    fn shader_paths(shader_dir: &Path) -> (PathBuf, PathBuf) {
+        (shader_dir.join("shader.vert"), shader_dir.join("shader.frag"))
+    }
+
+    fn shader_mtimes(vert_path: &Path, frag_path: &Path) -> (SystemTime, SystemTime) {
+        let mtime_of = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+        (mtime_of(vert_path), mtime_of(frag_path))
+    }
+
+    // Re-reads the shader files from disk and swaps in a freshly linked program,
+    // keeping the current one bound if the new source fails to compile/link so
+    // the window doesn't go blank mid-edit.
+    unsafe fn reload_shaders_if_changed(&mut self) {
+        let (vert_path, frag_path) = OpenGLContext::shader_paths(&self.shader_dir);
+        let mtimes = OpenGLContext::shader_mtimes(&vert_path, &frag_path);
+        if mtimes == self.shader_mtimes {
+            return;
         }
 
-        // Delete the shaders
-        for i in &compiled_shaders {
-            gl::DeleteShader(*i);
+        self.shader_mtimes = mtimes;
+        match build_shader_program(&self.gl, &vert_path, &frag_path) {
+            Ok(new_program) => {
+                self.gl.DeleteProgram(self.shader_program);
+                self.shader_program = new_program;
+            },
+            Err(e) => {
+                println!("ERROR::SHADER::HOT_RELOAD_FAILED\n{}\nkeeping previous program", e);
+            }
         }
+    }
 
-        // Return the final linked shader program
-        shader_program
+    unsafe fn set_uniform_mat4(&self, name: &str, value: &Matrix4<f32>) {
+        let c_name = CString::new(name).unwrap();
+        let location = self.gl.GetUniformLocation(self.shader_program, c_name.as_ptr());
+        self.gl.UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr());
     }
 }
 
-struct OpenGLApplication<'a> {
-    screen_width: u32,
-    screen_height: u32,
-    title: &'a str,
-    glfw: glfw::Glfw,
-    window: glfw::Window,
-    window_events: Receiver<(f64, glfw::WindowEvent)>,
-    opengl_context: OpenGLContext
+struct Camera {
+    position: Point3<f32>,
+    target: Point3<f32>,
+    fov: Deg<f32>
 }
 
-impl<'a> OpenGLApplication<'a> {
-    fn initialize(width: u32, height: u32, title: &'a str) -> Self {
-        let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
-        glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
-        glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+impl Camera {
+    fn new(position: Point3<f32>, target: Point3<f32>, fov: Deg<f32>) -> Self {
+        Self { position, target, fov }
+    }
 
-        let (mut window, events) = glfw.create_window(width, height, title, glfw::WindowMode::Windowed)
-            .expect("Failed to create GLFW Window");
+    fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(self.position, self.target, Vector3::unit_y())
+    }
+
+    fn projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+        Matrix4::from(PerspectiveFov {
+            fovy: Rad::from(self.fov),
+            aspect: aspect_ratio,
+            near: 0.1,
+            far: 100.0
+        })
+    }
+}
 
-        window.make_current();
-        window.set_key_polling(true);
-        window.set_framebuffer_size_polling(true);
+struct OpenGLApplication {
+    gl: Rc<gl::Gl>,
+    screen_width: u32,
+    screen_height: u32,
+    window: Window,
+    gl_surface: Surface<WindowSurface>,
+    gl_context: PossiblyCurrentContext,
+    opengl_context: OpenGLContext,
+    hot_reload_shaders: bool,
+    camera: Camera,
+    model: Matrix4<f32>,
+    text_renderer: text::TextRenderer,
+    automaton: Option<compute::Automaton>
+}
 
-        gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+impl OpenGLApplication {
+    fn initialize_with_shaders(gl: Rc<gl::Gl>, window: Window, gl_surface: Surface<WindowSurface>,
+        gl_context: PossiblyCurrentContext, shader_dir: &str, hot_reload_shaders: bool) -> Self {
+        let size = window.inner_size();
+        let opengl_context = unsafe { OpenGLContext::initialize(gl.clone(), shader_dir, TEXTURE_PATH) };
+        let camera = Camera::new(Point3::new(0.0, 0.0, 3.0), Point3::new(0.0, 0.0, 0.0), Deg(45.0));
+        let text_renderer = unsafe {
+            text::TextRenderer::initialize(gl.clone(), FONT_PATH, shader_dir, FONT_PIXEL_SIZE, size.width, size.height)
+        };
 
-        let opengl_context = unsafe { OpenGLContext::initialize() };
         Self {
-            screen_width: width,
-            screen_height: height,
-            title: title,
-            glfw: glfw,
-            window: window,
-            window_events: events,
-            opengl_context: opengl_context
+            gl,
+            screen_width: size.width,
+            screen_height: size.height,
+            window,
+            gl_surface,
+            gl_context,
+            opengl_context,
+            hot_reload_shaders,
+            camera,
+            model: Matrix4::identity(),
+            text_renderer,
+            automaton: None
         }
     }
 
-    fn run(&mut self) {
-        while !self.window.should_close() {
-            self.process_window_events();
+    // Demo entry point for the GPGPU cellular-automaton path: renders the
+    // compute-shader grid instead of the textured quad scene.
+    fn enable_automaton_demo(&mut self) {
+        self.automaton = Some(unsafe {
+            compute::Automaton::initialize(self.gl.clone(), SHADER_DIR, AUTOMATON_GRID_WIDTH, AUTOMATON_GRID_HEIGHT)
+        });
+    }
 
-            unsafe {
-                gl::ClearColor(0.2, 0.3, 0.3, 1.0);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
-                gl::UseProgram(self.opengl_context.shader_program);
-                gl::BindVertexArray(self.opengl_context.vao);
-                gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
-            }
+    fn draw_text(&self, s: &str, x: f32, y: f32, px: f32) {
+        unsafe { self.text_renderer.draw_text(s, x, y, px / FONT_PIXEL_SIZE as f32); }
+    }
 
-            self.window.swap_buffers();
-            self.glfw.poll_events();
+    fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
         }
+
+        self.screen_width = width;
+        self.screen_height = height;
+        self.gl_surface.resize(&self.gl_context,
+            NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap());
+        unsafe { self.gl.Viewport(0, 0, width as GLint, height as GLint); }
+        self.text_renderer.resize(width, height);
     }
 
-    fn process_window_events(&mut self) {
-        for (_, event) in glfw::flush_messages(&self.window_events) {
-            match event {
-                glfw::WindowEvent::FramebufferSize(width, height) => {
-                    unsafe {
-                        gl::Viewport(0, 0, width, height);
-                        self.screen_width = width as u32;
-                        self.screen_height = height as u32;
-                    } 
-                },
-                glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-                    self.window.set_should_close(true);
-                },
-                _ => {}
+    fn draw_frame(&mut self) {
+        unsafe {
+            if self.hot_reload_shaders {
+                self.opengl_context.reload_shaders_if_changed();
+            }
+
+            self.gl.ClearColor(0.2, 0.3, 0.3, 1.0);
+            self.gl.Clear(gl::COLOR_BUFFER_BIT);
+
+            if let Some(automaton) = self.automaton.as_mut() {
+                automaton.step();
+                automaton.render();
+            } else {
+                self.gl.UseProgram(self.opengl_context.shader_program);
+
+                let aspect_ratio = self.screen_width as f32 / self.screen_height as f32;
+                let view_proj = self.camera.projection_matrix(aspect_ratio) * self.camera.view_matrix();
+                self.opengl_context.set_uniform_mat4("proj", &view_proj);
+                self.opengl_context.set_uniform_mat4("mod", &self.model);
+
+                self.gl.ActiveTexture(gl::TEXTURE0);
+                self.gl.BindTexture(gl::TEXTURE_2D, self.opengl_context.texture.id);
+                for mesh in &self.opengl_context.meshes {
+                    mesh.draw();
+                }
             }
         }
+
+        self.draw_text("Hello, engine!", 10.0, 30.0, FONT_PIXEL_SIZE as f32);
+
+        self.window.request_redraw();
+        self.gl_surface.swap_buffers(&self.gl_context).expect("Failed to swap buffers");
+    }
+}
+
+// Builds the window + GL context/surface through glutin-winit, picking a
+// config with the highest MSAA sample count the platform offers.
+fn build_window_and_context(event_loop: &EventLoop<()>) ->
+    (Window, Rc<gl::Gl>, Surface<WindowSurface>, PossiblyCurrentContext) {
+    let window_builder = WindowBuilder::new()
+        .with_title("OpenGL Learning")
+        .with_inner_size(winit::dpi::PhysicalSize::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+
+    #[cfg_attr(not(egl), allow(unused_mut))]
+    let mut template = ConfigTemplateBuilder::new();
+    // EGL drivers are more likely than GLX to fall back to a software
+    // rasterizer on headless/virtual displays; bias towards a GPU-backed
+    // config when we know EGL is in play.
+    #[cfg(egl)]
+    {
+        template = template.prefer_hardware_accelerated(Some(true));
     }
+    let display_builder = DisplayBuilder::new().with_window_builder(Some(window_builder));
+
+    let (window, gl_config) = display_builder
+        .build(event_loop, template, |configs| {
+            configs.reduce(|accum, config| {
+                if config.num_samples() > accum.num_samples() { config } else { accum }
+            }).unwrap()
+        })
+        .expect("Failed to create window/config via glutin-winit");
+    let window = window.expect("glutin-winit did not produce a window");
+
+    let raw_window_handle = window.raw_window_handle();
+    let gl_display = gl_config.display();
+
+    let context_attributes = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::OpenGl(Some(Version::new(4, 3))))
+        .build(Some(raw_window_handle));
+
+    let not_current_gl_context = unsafe {
+        gl_display.create_context(&gl_config, &context_attributes)
+            .expect("Failed to create GL context")
+    };
+
+    let attrs = window.build_surface_attributes(Default::default());
+    let gl_surface = unsafe {
+        gl_display.create_window_surface(&gl_config, &attrs)
+            .expect("Failed to create window surface")
+    };
+
+    let gl_context = not_current_gl_context.make_current(&gl_surface)
+        .expect("Failed to make GL context current");
+
+    gl_surface.set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
+        .expect("Failed to set swap interval");
+
+    let gl = Rc::new(gl::Gl::load_with(|symbol| {
+        let c_str = CString::new(symbol).unwrap();
+        gl_display.get_proc_address(c_str.as_c_str()) as *const _
+    }));
+
+    // Some Wayland compositors never deliver the implicit first RedrawRequested
+    // that X11/Windows give us for free, so ask for one explicitly here.
+    #[cfg(wayland_platform)]
+    window.request_redraw();
+
+    (window, gl, gl_surface, gl_context)
 }
 
 pub fn main() {
-    let mut app: OpenGLApplication = OpenGLApplication::initialize(SCREEN_WIDTH, SCREEN_HEIGHT, "OpenGL Learning");
-    app.run();
+    let event_loop = EventLoop::new().expect("Failed to create winit event loop");
+    let (window, gl, gl_surface, gl_context) = build_window_and_context(&event_loop);
+
+    let mut app = OpenGLApplication::initialize_with_shaders(
+        gl, window, gl_surface, gl_context, SHADER_DIR, true);
+
+    // `--automaton` swaps the textured-quad scene for the GPGPU cellular-automaton demo.
+    if std::env::args().any(|arg| arg == "--automaton") {
+        app.enable_automaton_demo();
+    }
+
+    event_loop.run(move |event, window_target| {
+        match event {
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => window_target.exit(),
+            Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
+                app.resize(size.width, size.height);
+            },
+            Event::WindowEvent { event: WindowEvent::KeyboardInput { event: key_event, .. }, .. }
+                if key_event.state == ElementState::Pressed
+                    && key_event.physical_key == PhysicalKey::Code(KeyCode::Escape) => {
+                window_target.exit();
+            },
+            Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => {
+                app.draw_frame();
+            },
+            _ => {}
+        }
+    }).expect("Event loop exited with an error");
 }
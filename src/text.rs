@@ -0,0 +1,189 @@
+// Glyph-atlas text rendering: rasterizes glyphs with freetype, caches each one
+// into its own GL texture, and draws them as alpha-blended textured quads
+// through a dedicated orthographic-projection shader pair.
+
+use crate::gl;
+use crate::gl::types::*;
+use crate::cgmath::{Matrix4, Matrix, ortho};
+use crate::build_shader_program;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+use std::rc::Rc;
+
+struct Glyph {
+    texture_id: GLuint,
+    width: i32,
+    height: i32,
+    bearing_x: i32,
+    bearing_y: i32,
+    advance: i64
+}
+
+pub struct TextRenderer {
+    gl: Rc<gl::Gl>,
+    shader_program: GLuint,
+    vao: u32,
+    vbo: u32,
+    glyphs: HashMap<char, Glyph>,
+    projection: Matrix4<f32>
+}
+
+impl TextRenderer {
+    pub unsafe fn initialize(gl: Rc<gl::Gl>, font_path: &str, shader_dir: &str, pixel_size: u32, screen_width: u32, screen_height: u32) -> Self {
+        let shader_program = build_shader_program(&gl,
+            &Path::new(shader_dir).join("text.vert"),
+            &Path::new(shader_dir).join("text.frag"))
+            .expect("Failed to build text shader program");
+
+        let glyphs = TextRenderer::rasterize_ascii(&gl, font_path, pixel_size);
+
+        let (mut vao, mut vbo) = (0, 0);
+        gl.GenVertexArrays(1, &mut vao);
+        gl.GenBuffers(1, &mut vbo);
+        gl.BindVertexArray(vao);
+        gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl.BufferData(gl::ARRAY_BUFFER,
+            (6 * 4 * mem::size_of::<GLfloat>()) as GLsizeiptr,
+            ptr::null(),
+            gl::DYNAMIC_DRAW);
+        gl.VertexAttribPointer(0, 4, gl::FLOAT, gl::FALSE, 4 * mem::size_of::<GLfloat>() as GLsizei, ptr::null());
+        gl.EnableVertexAttribArray(0);
+        gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl.BindVertexArray(0);
+
+        Self {
+            gl,
+            shader_program,
+            vao,
+            vbo,
+            glyphs,
+            projection: TextRenderer::ortho_projection(screen_width, screen_height)
+        }
+    }
+
+    // Recomputed whenever the framebuffer is resized so on-screen text keeps
+    // its pixel-accurate size regardless of window dimensions.
+    pub fn resize(&mut self, screen_width: u32, screen_height: u32) {
+        self.projection = TextRenderer::ortho_projection(screen_width, screen_height);
+    }
+
+    fn ortho_projection(screen_width: u32, screen_height: u32) -> Matrix4<f32> {
+        ortho(0.0, screen_width as f32, screen_height as f32, 0.0, -1.0, 1.0)
+    }
+
+    // Returns an empty atlas (so `draw_text` simply draws nothing) instead of
+    // panicking when the font asset is missing or fails to load, so a missing
+    // font degrades to no on-screen text rather than crashing the app.
+    fn rasterize_ascii(gl: &gl::Gl, font_path: &str, pixel_size: u32) -> HashMap<char, Glyph> {
+        let library = crate::freetype::Library::init().expect("Failed to init freetype");
+
+        let face = match library.new_face(font_path, 0) {
+            Ok(face) => face,
+            Err(e) => {
+                println!("WARNING::TEXT::FONT_LOAD_FAILED\nFailed to load font {}: {}\ntext rendering disabled", font_path, e);
+                return HashMap::new();
+            }
+        };
+
+        if let Err(e) = face.set_pixel_sizes(0, pixel_size) {
+            println!("WARNING::TEXT::FONT_LOAD_FAILED\nFailed to set pixel size for font {}: {}\ntext rendering disabled", font_path, e);
+            return HashMap::new();
+        }
+
+        let mut glyphs = HashMap::new();
+        unsafe { gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1); }
+
+        for c in 0u8..128 {
+            face.load_char(c as usize, crate::freetype::face::LoadFlag::RENDER)
+                .unwrap_or_else(|e| panic!("Failed to load glyph {}: {}", c as char, e));
+
+            let glyph_slot = face.glyph();
+            let bitmap = glyph_slot.bitmap();
+            let width = bitmap.width();
+            let height = bitmap.rows();
+
+            let mut texture_id = 0;
+            unsafe {
+                gl.GenTextures(1, &mut texture_id);
+                gl.BindTexture(gl::TEXTURE_2D, texture_id);
+                gl.TexImage2D(gl::TEXTURE_2D, 0, gl::RED as GLint, width, height,
+                    0, gl::RED, gl::UNSIGNED_BYTE, bitmap.buffer().as_ptr() as *const c_void);
+
+                gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+                gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+                gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+                gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+                gl.BindTexture(gl::TEXTURE_2D, 0);
+            }
+
+            glyphs.insert(c as char, Glyph {
+                texture_id,
+                width,
+                height,
+                bearing_x: glyph_slot.bitmap_left(),
+                bearing_y: glyph_slot.bitmap_top(),
+                advance: glyph_slot.advance().x
+            });
+        }
+
+        glyphs
+    }
+
+    // Draws `text` with its baseline's left edge at (x, y) in screen pixels,
+    // scaled relative to the pixel size the atlas was rasterized at.
+    pub unsafe fn draw_text(&self, text: &str, mut x: f32, y: f32, scale: f32) {
+        let gl = &self.gl;
+        gl.UseProgram(self.shader_program);
+
+        let c_name = CString::new("projection").unwrap();
+        let location = gl.GetUniformLocation(self.shader_program, c_name.as_ptr());
+        gl.UniformMatrix4fv(location, 1, gl::FALSE, self.projection.as_ptr());
+
+        let c_color = CString::new("textColor").unwrap();
+        let color_location = gl.GetUniformLocation(self.shader_program, c_color.as_ptr());
+        gl.Uniform4f(color_location, 1.0, 1.0, 1.0, 1.0);
+
+        gl.ActiveTexture(gl::TEXTURE0);
+        gl.BindVertexArray(self.vao);
+
+        for ch in text.chars() {
+            let glyph = match self.glyphs.get(&ch) {
+                Some(g) => g,
+                None => continue
+            };
+
+            let xpos = x + glyph.bearing_x as f32 * scale;
+            let ypos = y - glyph.bearing_y as f32 * scale;
+            let w = glyph.width as f32 * scale;
+            let h = glyph.height as f32 * scale;
+
+            let vertices: [f32; 24] = [
+                xpos,     ypos,     0.0, 0.0,
+                xpos,     ypos + h, 0.0, 1.0,
+                xpos + w, ypos + h, 1.0, 1.0,
+
+                xpos,     ypos,     0.0, 0.0,
+                xpos + w, ypos + h, 1.0, 1.0,
+                xpos + w, ypos,     1.0, 0.0
+            ];
+
+            gl.BindTexture(gl::TEXTURE_2D, glyph.texture_id);
+            gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl.BufferSubData(gl::ARRAY_BUFFER, 0, (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                &vertices[0] as *const f32 as *const c_void);
+            gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+
+            gl.DrawArrays(gl::TRIANGLES, 0, 6);
+
+            // advance is in 1/64th pixels
+            x += (glyph.advance >> 6) as f32 * scale;
+        }
+
+        gl.BindVertexArray(0);
+        gl.BindTexture(gl::TEXTURE_2D, 0);
+    }
+}